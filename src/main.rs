@@ -1,25 +1,39 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     io::{self, Write},
+    path::Path,
 };
 
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+/// Default path the inventory is loaded from and saved to.
+const DEFAULT_DATA_FILE: &str = "inventory.json";
+
 fn main() {
     // Create/Inicialize Inventory
-    let mut inventory = Inventory::new();
-
-    let first_product = Product {
-        id: Uuid::new_v4(),
-        name: "Phone S".to_string(),
-        brand: Brand::Samsung,
-        price: 1000.5,
-        stock: 10,
-        updated_at: OffsetDateTime::now_local().unwrap(),
-    };
+    let mut data_file_path = DEFAULT_DATA_FILE.to_string();
 
-    inventory.products.insert(first_product.id, first_product);
+    let mut inventory = if Path::new(&data_file_path).exists() {
+        Inventory::load_from_file(&data_file_path)
+    } else {
+        let mut inventory = Inventory::new();
+
+        let first_product = Product {
+            id: Uuid::new_v4(),
+            name: "Phone S".to_string(),
+            brand: Brand::Samsung,
+            price: 1000.5,
+            stock: 10,
+            updated_at: OffsetDateTime::now_local().unwrap(),
+            components: Vec::new(),
+        };
+
+        inventory.add_product(first_product);
+        inventory
+    };
 
     loop {
         print!("\x1B[2J\x1B[1;1H"); // Clear screen
@@ -29,7 +43,13 @@ fn main() {
         println!("3. Create");
         println!("4. Update");
         println!("5. Delete");
-        println!("6. Exit");
+        println!("6. Remove all out-of-stock");
+        println!("7. Remove below threshold");
+        println!("8. Clear a whole brand");
+        println!("9. Build/update a bundle");
+        println!("10. List by price range");
+        println!("11. Set data file path");
+        println!("12. Exit");
 
         /*
         print! macro doesn't automatically flush (clear) the output buffer,
@@ -57,7 +77,8 @@ fn main() {
         match input {
             1 => {
                 print!("\x1B[2J\x1B[1;1H");
-                inventory.see_all();
+                let sort_by = handler_input_sort_by();
+                inventory.see_all(sort_by);
                 println!("Enter to continue...");
                 io::stdin().read_line(&mut String::new()).unwrap();
             }
@@ -76,6 +97,7 @@ fn main() {
             3 => {
                 let (name, brand, price, stock) = handler_input_data();
                 inventory.create(&name, brand, price, stock);
+                save_inventory(&inventory, &data_file_path);
             }
             4 => {
                 let id = handler_input_id();
@@ -88,33 +110,109 @@ fn main() {
                     stock,
                 };
                 inventory.update(id, new_data);
+                save_inventory(&inventory, &data_file_path);
             }
             5 => {
                 let id = handler_input_id();
                 inventory.delete(id);
+                save_inventory(&inventory, &data_file_path);
+            }
+            6 => {
+                inventory.remove_out_of_stock();
+                save_inventory(&inventory, &data_file_path);
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            7 => {
+                let threshold = handler_input_stock_threshold();
+                inventory.remove_below_threshold(threshold);
+                save_inventory(&inventory, &data_file_path);
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            8 => {
+                let brand = handler_input_brand();
+                inventory.clear_brand(brand);
+                save_inventory(&inventory, &data_file_path);
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            9 => {
+                let parent_id = handler_input_id();
+                let components = handler_input_components();
+                if inventory.set_bundle_components(parent_id, components) {
+                    inventory.rebuild_bundle(parent_id);
+                    save_inventory(&inventory, &data_file_path);
+                }
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            10 => {
+                print!("\x1B[2J\x1B[1;1H");
+                let (min, max) = handler_input_price_range();
+                inventory.list_by_price_range(min, max);
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            11 => {
+                let new_path = handler_input_data_file_path();
+
+                if Path::new(&new_path).exists() {
+                    inventory = Inventory::load_from_file(&new_path);
+                    println!("Loaded inventory from '{}'.", new_path);
+                } else {
+                    save_inventory(&inventory, &new_path);
+                    println!("Now saving inventory to '{}'.", new_path);
+                }
+                data_file_path = new_path;
+
+                println!("Enter to continue...");
+                io::stdin().read_line(&mut String::new()).unwrap();
+            }
+            12 => {
+                save_inventory(&inventory, &data_file_path);
+                break;
             }
-            6 => break,
             _ => println!("Invalid input."),
         }
     }
 }
 
 // ENUM, STRUCTS AND IMPL
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 enum Brand {
     Apple,
     Google,
     Samsung,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
+enum SortBy {
+    Name,
+    Price,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Product {
     id: Uuid,
     name: String,
     brand: Brand,
     price: f32,
     stock: u16,
+    #[serde(with = "time::serde::rfc3339")]
     updated_at: OffsetDateTime,
+    /// IDs of other products in the same inventory that make up this bundle.
+    /// Empty for a regular (non-bundle) product.
+    components: Vec<Uuid>,
+}
+
+/// On-disk shape of an `Inventory`. `products` is stored as a list rather
+/// than keyed by `Uuid` so it round-trips through JSON without relying on
+/// map-key encoding; `name_index`/`price_index` are rebuilt on load instead
+/// of being persisted.
+#[derive(Debug, Serialize, Deserialize)]
+struct InventoryFile {
+    products: Vec<Product>,
 }
 
 struct ProductDto {
@@ -126,6 +224,13 @@ struct ProductDto {
 
 struct Inventory {
     products: HashMap<Uuid, Product>,
+    /// Secondary index over lowercased product names, kept in sync with
+    /// `products` by `create`/`update`/`delete`, so name lookups are an
+    /// ordered range scan instead of a linear one.
+    name_index: BTreeMap<String, Vec<Uuid>>,
+    /// Secondary index over product prices, kept in sync the same way,
+    /// backing the price-range query.
+    price_index: BTreeMap<OrderedFloat<f32>, Vec<Uuid>>,
 }
 
 // self: Would take complete ownership of the instance (consuming it)
@@ -137,6 +242,83 @@ impl Inventory {
     fn new() -> Self {
         Inventory {
             products: HashMap::new(),
+            name_index: BTreeMap::new(),
+            price_index: BTreeMap::new(),
+        }
+    }
+
+    /// Loads an inventory from a JSON file at `path`. Starts from an empty
+    /// inventory (rather than panicking) if the file is missing or corrupt.
+    fn load_from_file(path: &str) -> Self {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Inventory::new(),
+        };
+
+        let file_data: InventoryFile = match serde_json::from_str(&contents) {
+            Ok(file_data) => file_data,
+            Err(_) => {
+                println!("Warning: '{}' is corrupted; starting with an empty inventory.", path);
+                return Inventory::new();
+            }
+        };
+
+        let mut inventory = Inventory::new();
+        for product in file_data.products {
+            inventory.add_product(product);
+        }
+        inventory
+    }
+
+    /// Writes the inventory to `path` as JSON.
+    fn save_to_file(&self, path: &str) -> io::Result<()> {
+        let file_data = InventoryFile {
+            products: self.products.values().cloned().collect(),
+        };
+        let json = serde_json::to_string_pretty(&file_data).expect("Failed to serialize inventory");
+        std::fs::write(path, json)
+    }
+
+    /// Inserts `product` into `products` and its name/price indexes.
+    fn add_product(&mut self, product: Product) {
+        let id = product.id;
+        self.products.insert(id, product);
+        self.index_insert_for(id);
+    }
+
+    /// Removes a product from `products` and its indexes, returning it.
+    fn remove_product(&mut self, id: &Uuid) -> Option<Product> {
+        let product = self.products.remove(id)?;
+        self.index_remove_entries(&product.name.to_lowercase(), OrderedFloat(product.price), product.id);
+        Some(product)
+    }
+
+    /// Adds `id` to the name/price index buckets for its *current* data in
+    /// `products`. Call after the product has already been inserted/updated.
+    fn index_insert_for(&mut self, id: Uuid) {
+        let (name_lower, price) = {
+            let product = self.products.get(&id).unwrap();
+            (product.name.to_lowercase(), OrderedFloat(product.price))
+        };
+        self.name_index.entry(name_lower).or_default().push(id);
+        self.price_index.entry(price).or_default().push(id);
+    }
+
+    /// Removes `id` from the name/price index buckets keyed by the given
+    /// (pre-update) name/price, dropping empty buckets.
+    fn index_remove_entries(&mut self, name_lower: &str, price: OrderedFloat<f32>, id: Uuid) {
+        if let Some(ids) = self.name_index.get_mut(name_lower) {
+            ids.retain(|existing| *existing != id);
+            if ids.is_empty() {
+                self.name_index.remove(name_lower);
+            }
+        }
+
+        if let Some(ids) = self.price_index.get_mut(&price) {
+            ids.retain(|existing| *existing != id);
+            if ids.is_empty() {
+                self.price_index.remove(&price);
+            }
         }
     }
 
@@ -144,15 +326,26 @@ impl Inventory {
     // This method only needs to read data from Inventory without modifying it
     // Does not allow modifying the Inventory structure or its contents
     // Allows other methods or parts of the code to continue reading the data simultaneously
-    fn see_all(&self) {
+    /// Prints every product, optionally ordered by the name or price index
+    /// instead of the hashmap's arbitrary iteration order.
+    fn see_all(&self, sort_by: Option<SortBy>) {
         //println!("Products: {:?}", self.products);
 
         if self.products.is_empty() {
             return println!("No products");
         }
 
+        let ordered_ids: Vec<Uuid> = match sort_by {
+            Some(SortBy::Name) => self.name_index.values().flatten().copied().collect(),
+            Some(SortBy::Price) => self.price_index.values().flatten().copied().collect(),
+            None => self.products.keys().copied().collect(),
+        };
+
         let mut counter: u16 = 0;
-        for product in self.products.values() {
+        for id in ordered_ids {
+            let Some(product) = self.products.get(&id) else {
+                continue;
+            };
             let uuid_str = product.id.to_string();
             counter += 1;
 
@@ -169,21 +362,86 @@ impl Inventory {
         }
     }
 
+    /// Returns every product whose lowercased name starts with `query`
+    /// (case-insensitive), via an ordered range scan over `name_index`
+    /// instead of a linear scan that stops at the first hit.
     fn search_by_name(&self, query: &str) {
         if self.products.is_empty() {
             return println!("No products");
         }
 
-        for item in self.products.values() {
-            if item.name.to_lowercase().contains(&query.to_lowercase()) {
-                return println!(
+        let query_lower = query.to_lowercase();
+        let matching_ids: Vec<Uuid> = match Self::prefix_upper_bound(&query_lower) {
+            Some(upper) => self
+                .name_index
+                .range(query_lower.clone()..upper)
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect(),
+            None => self
+                .name_index
+                .range(query_lower.clone()..)
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect(),
+        };
+
+        if matching_ids.is_empty() {
+            return println!("'{}' not found.", query);
+        }
+
+        for id in matching_ids {
+            if let Some(product) = self.products.get(&id) {
+                println!(
                     "ID: {}. Name: {} - Brand: {:?}.",
-                    item.id, item.name, item.brand
+                    product.id, product.name, product.brand
                 );
             }
         }
+    }
+
+    /// Prints every product whose price falls in `[min, max]`, via an
+    /// ordered range scan over `price_index`.
+    fn list_by_price_range(&self, min: f32, max: f32) {
+        if self.products.is_empty() {
+            return println!("No products");
+        }
+
+        let mut counter: u16 = 0;
+        for (_, ids) in self.price_index.range(OrderedFloat(min)..=OrderedFloat(max)) {
+            for id in ids {
+                let Some(product) = self.products.get(id) else {
+                    continue;
+                };
+                counter += 1;
+                println!(
+                    "{}. ID: {}. Name: {}, brand: {:?}, price {}, stock: {}",
+                    counter,
+                    product.id,
+                    product.name,
+                    product.brand,
+                    product.price,
+                    product.stock
+                );
+            }
+        }
+
+        if counter == 0 {
+            println!("No products in range [{}, {}].", min, max);
+        }
+    }
 
-        println!("'{}' not found.", query);
+    /// Smallest string greater than every string with the given `prefix`,
+    /// used as the exclusive upper bound of a `BTreeMap` prefix range scan.
+    /// Returns `None` when `prefix` is empty or made only of `char::MAX`,
+    /// in which case the range has no upper bound.
+    fn prefix_upper_bound(prefix: &str) -> Option<String> {
+        let mut chars: Vec<char> = prefix.chars().collect();
+        while let Some(last) = chars.pop() {
+            if let Some(next) = char::from_u32(last as u32 + 1) {
+                chars.push(next);
+                return Some(chars.into_iter().collect());
+            }
+        }
+        None
     }
 
     /// &str is only a temporary reference
@@ -198,9 +456,10 @@ impl Inventory {
             price,
             stock,
             updated_at: OffsetDateTime::now_local().unwrap(),
+            components: Vec::new(),
         };
 
-        self.products.insert(new.id, new);
+        self.add_product(new);
     }
 
     // &mut self (mutable reference)
@@ -209,28 +468,153 @@ impl Inventory {
     // Ensures no other part of the code is reading or modifying the Inventory
     // while this method is executing
     fn update(&mut self, id: Uuid, new_data: ProductDto) {
-        if let Some(product) = self.products.get_mut(&id) {
-            //if let Some(new_price) = price {
-            //    product.price = new_price;
-            //}
-
-            product.name = new_data.name;
-            product.brand = new_data.brand;
-            product.price = new_data.price;
-            product.stock = new_data.stock;
-            product.updated_at = OffsetDateTime::now_local().unwrap();
-        }
+        let Some(product) = self.products.get(&id) else {
+            return;
+        };
+        let (old_name_lower, old_price) = (product.name.to_lowercase(), OrderedFloat(product.price));
+        self.index_remove_entries(&old_name_lower, old_price, id);
+
+        let product = self.products.get_mut(&id).unwrap();
+        //if let Some(new_price) = price {
+        //    product.price = new_price;
+        //}
+
+        product.name = new_data.name;
+        product.brand = new_data.brand;
+        product.price = new_data.price;
+        product.stock = new_data.stock;
+        product.updated_at = OffsetDateTime::now_local().unwrap();
+
+        self.index_insert_for(id);
     }
 
     fn delete(&mut self, id: Uuid) {
         //self.products.remove(&id);
 
-        if self.products.remove(&id).is_some() {
+        if self.remove_product(&id).is_some() {
             println!("Product with ID {} deleted.", id);
         } else {
             println!("Product not found.");
         }
     }
+
+    /// Removes every product matching `pred` and returns the removed products.
+    ///
+    /// Collects the matching IDs in a first pass (immutable borrow of `products`),
+    /// then removes each one in a second pass, so the map is never mutated while
+    /// it's being iterated.
+    fn extract_where<F: Fn(&Product) -> bool>(&mut self, pred: F) -> Vec<Product> {
+        let matching_ids: Vec<Uuid> = self
+            .products
+            .iter()
+            .filter(|(_, product)| pred(product))
+            .map(|(id, _)| *id)
+            .collect();
+
+        matching_ids
+            .into_iter()
+            .filter_map(|id| self.remove_product(&id))
+            .collect()
+    }
+
+    fn remove_out_of_stock(&mut self) {
+        let removed = self.extract_where(|product| product.stock == 0);
+        Self::print_removed_summary("out-of-stock", &removed);
+    }
+
+    fn remove_below_threshold(&mut self, threshold: u16) {
+        let removed = self.extract_where(|product| product.stock < threshold);
+        Self::print_removed_summary(&format!("stock below {}", threshold), &removed);
+    }
+
+    fn clear_brand(&mut self, brand: Brand) {
+        let removed = self.extract_where(|product| product.brand == brand);
+        Self::print_removed_summary(&format!("brand {:?}", brand), &removed);
+    }
+
+    /// Sets the component IDs that make up a bundle, skipping a component
+    /// that references the parent itself. Returns `false` if `parent_id`
+    /// doesn't exist.
+    fn set_bundle_components(&mut self, parent_id: Uuid, components: Vec<Uuid>) -> bool {
+        if !self.products.contains_key(&parent_id) {
+            println!("Product not found.");
+            return false;
+        }
+
+        let components: Vec<Uuid> = components
+            .into_iter()
+            .filter(|component_id| {
+                let is_self_reference = *component_id == parent_id;
+                if is_self_reference {
+                    println!("A product can't list itself as a component; skipping.");
+                }
+                !is_self_reference
+            })
+            .collect();
+
+        self.products.get_mut(&parent_id).unwrap().components = components;
+        true
+    }
+
+    /// Recomputes a bundle's price (sum of its components' prices) and stock
+    /// (minimum buildable from its components' stock).
+    ///
+    /// First takes an immutable borrow to look up each component ID and
+    /// collect its `(price, stock)`, letting that borrow end; then takes a
+    /// `get_mut` on the parent to write the aggregated results back. This
+    /// avoids borrowing `self.products` both immutably and mutably at once.
+    fn rebuild_bundle(&mut self, parent_id: Uuid) {
+        let Some(parent) = self.products.get(&parent_id) else {
+            println!("Product not found.");
+            return;
+        };
+
+        let mut price_and_stock: Vec<(f32, u16)> = Vec::new();
+        for component_id in &parent.components {
+            match self.products.get(component_id) {
+                Some(component) => price_and_stock.push((component.price, component.stock)),
+                None => println!("Warning: component {} not found, skipping.", component_id),
+            }
+        }
+
+        if price_and_stock.is_empty() {
+            println!("Bundle has no valid components; nothing to roll up.");
+            return;
+        }
+
+        let total_price: f32 = price_and_stock.iter().map(|(price, _)| price).sum();
+        let min_stock: u16 = price_and_stock.iter().map(|(_, stock)| *stock).min().unwrap();
+
+        let (name_lower, old_price) = {
+            let parent = self.products.get(&parent_id).unwrap();
+            (parent.name.to_lowercase(), OrderedFloat(parent.price))
+        };
+        self.index_remove_entries(&name_lower, old_price, parent_id);
+
+        let parent = self.products.get_mut(&parent_id).unwrap();
+        parent.price = total_price;
+        parent.stock = min_stock;
+        parent.updated_at = OffsetDateTime::now_local().unwrap();
+
+        self.index_insert_for(parent_id);
+
+        println!(
+            "Bundle rolled up: price {}, buildable stock {}.",
+            total_price, min_stock
+        );
+    }
+
+    fn print_removed_summary(reason: &str, removed: &[Product]) {
+        if removed.is_empty() {
+            println!("No products removed ({}).", reason);
+            return;
+        }
+
+        println!("Removed {} product(s) ({}):", removed.len(), reason);
+        for product in removed {
+            println!("- {} (stock: {})", product.name, product.stock);
+        }
+    }
 }
 
 // HELPERS
@@ -267,22 +651,7 @@ fn handler_input_data() -> (String, Brand, f32, u16) {
     let name: String = name.trim().to_string();
 
     // Brand
-    let brand = loop {
-        let mut brand_input = String::new();
-        print!("Brand (Apple, Samsung or Google): ");
-        io::stdout().flush().unwrap();
-        io::stdin()
-            .read_line(&mut brand_input)
-            .expect("Failed to read BRAND");
-        let brand_input = brand_input.trim().to_lowercase();
-
-        match brand_input.as_str() {
-            "apple" => break Brand::Apple,
-            "google" => break Brand::Google,
-            "samsung" => break Brand::Samsung,
-            _ => println!("Invalid brand! Please enter Apple, Google or Samsung."),
-        }
-    };
+    let brand = handler_input_brand();
 
     // Price
     let price = loop {
@@ -317,6 +686,135 @@ fn handler_input_data() -> (String, Brand, f32, u16) {
     (name, brand, price, stock)
 }
 
+fn handler_input_brand() -> Brand {
+    loop {
+        let mut brand_input = String::new();
+        print!("Brand (Apple, Samsung or Google): ");
+        io::stdout().flush().unwrap();
+        io::stdin()
+            .read_line(&mut brand_input)
+            .expect("Failed to read BRAND");
+        let brand_input = brand_input.trim().to_lowercase();
+
+        match brand_input.as_str() {
+            "apple" => break Brand::Apple,
+            "google" => break Brand::Google,
+            "samsung" => break Brand::Samsung,
+            _ => println!("Invalid brand! Please enter Apple, Google or Samsung."),
+        }
+    }
+}
+
+fn handler_input_stock_threshold() -> u16 {
+    loop {
+        let mut threshold_input = String::new();
+        print!("Stock threshold: ");
+        io::stdout().flush().unwrap();
+        io::stdin()
+            .read_line(&mut threshold_input)
+            .expect("Failed to read THRESHOLD");
+
+        match threshold_input.trim().parse::<u16>() {
+            Ok(threshold) => break threshold,
+            Err(_) => println!("Invalid threshold! Enter a positive whole number."),
+        }
+    }
+}
+
+fn handler_input_components() -> Vec<Uuid> {
+    let mut components: Vec<Uuid> = Vec::new();
+
+    loop {
+        let mut component_input = String::new();
+        print!("Component ID (blank to finish): ");
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut component_input).unwrap();
+        let component_input = component_input.trim();
+
+        if component_input.is_empty() {
+            break;
+        }
+
+        match component_input.parse::<Uuid>() {
+            Ok(id) => components.push(id),
+            Err(_) => println!("Invalid ID! Please enter a valid UUID."),
+        }
+    }
+
+    components
+}
+
+/// Saves `inventory` to `path`, printing a warning instead of failing if
+/// the write doesn't succeed.
+fn save_inventory(inventory: &Inventory, path: &str) {
+    if let Err(err) = inventory.save_to_file(path) {
+        println!("Warning: failed to save inventory to '{}': {}", path, err);
+    }
+}
+
+fn handler_input_data_file_path() -> String {
+    loop {
+        let mut path_input = String::new();
+        print!("Data file path: ");
+        io::stdout().flush().unwrap();
+        io::stdin().read_line(&mut path_input).unwrap();
+        let path_input = path_input.trim();
+
+        if path_input.is_empty() {
+            println!("Path can't be empty.");
+            continue;
+        }
+
+        break path_input.to_string();
+    }
+}
+
+fn handler_input_sort_by() -> Option<SortBy> {
+    print!("Sort by (name/price/blank for none): ");
+    io::stdout().flush().unwrap();
+
+    let mut sort_input = String::new();
+    io::stdin().read_line(&mut sort_input).unwrap();
+
+    match sort_input.trim().to_lowercase().as_str() {
+        "name" => Some(SortBy::Name),
+        "price" => Some(SortBy::Price),
+        _ => None,
+    }
+}
+
+fn handler_input_price_range() -> (f32, f32) {
+    let min = loop {
+        let mut min_input = String::new();
+        print!("Min price: ");
+        io::stdout().flush().unwrap();
+        io::stdin()
+            .read_line(&mut min_input)
+            .expect("Failed to read MIN PRICE");
+
+        match min_input.trim().parse::<f32>() {
+            Ok(p) if p >= 0.0 => break p,
+            _ => println!("Invalid price! Enter a non-negative number."),
+        }
+    };
+
+    let max = loop {
+        let mut max_input = String::new();
+        print!("Max price: ");
+        io::stdout().flush().unwrap();
+        io::stdin()
+            .read_line(&mut max_input)
+            .expect("Failed to read MAX PRICE");
+
+        match max_input.trim().parse::<f32>() {
+            Ok(p) if p >= min => break p,
+            _ => println!("Invalid price! Enter a number >= min price."),
+        }
+    };
+
+    (min, max)
+}
+
 fn handler_input_id() -> Uuid {
     loop {
         let mut id_input = String::new();